@@ -1,14 +1,27 @@
 use crate::future::poll_fn;
-use crate::sync::semaphore_ll::{AcquireError, Permit, Semaphore};
+use crate::sync::semaphore_ll::{AcquireError, Permit, Semaphore, TryAcquireError};
 use std::cell::UnsafeCell;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
 use std::ops;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
-#[cfg(not(loom))]
-const MAX_READS: usize = 32;
-
-#[cfg(loom)]
-const MAX_READS: usize = 10;
+// A single `Permit::{poll_acquire,try_acquire,release}` call only ever
+// grants or releases a `u16`-sized batch at once (this is the type
+// `semaphore_ll` exposes for those calls). A `MAX_READS` past `u16::MAX`
+// would therefore not fit in a single such call, so instead
+// of asking the semaphore for the whole write lock in one call, a write
+// lock's hold is split across as many `ACQUIRE_CHUNK`-sized `Permit`s as it
+// takes to add up to `MAX_READS` (see `ReleasingPermit`). `read()` still
+// only ever needs a single permit. This raises the reader ceiling to
+// `MAX_READS` (2^29 - 1) concurrent readers without any single
+// acquire/release call ever exceeding the `u16` batch size.
+const ACQUIRE_CHUNK: u16 = u16::MAX >> 3;
+const MAX_READS: usize = (u32::MAX >> 3) as usize;
 
 /// An asynchronous reader-writer lock
 ///
@@ -99,27 +112,209 @@ pub struct RwLockWriteGuard<'a, T> {
     lock: &'a RwLock<T>,
 }
 
-// Wrapper arround Permit that releases on Drop
+/// Owned RAII structure used to release the shared read access of a lock
+/// when dropped.
+///
+/// This structure is created by the [`read_owned`] method on [`RwLock`].
+///
+/// [`read_owned`]: struct.RwLock.html#method.read_owned
+#[derive(Debug)]
+pub struct OwnedRwLockReadGuard<T> {
+    permit: ReleasingPermit<'static, T>,
+}
+
+/// Owned RAII structure used to release the exclusive write access of a lock
+/// when dropped.
+///
+/// This structure is created by the [`write_owned`] method on [`RwLock`].
+///
+/// [`write_owned`]: struct.RwLock.html#method.write_owned
+#[derive(Debug)]
+pub struct OwnedRwLockWriteGuard<T> {
+    permit: ReleasingPermit<'static, T>,
+}
+
+/// RAII structure used to release the shared read access of a lock when
+/// dropped, and which can point at a component of the locked data.
+///
+/// This structure is created by the [`map`] method on [`RwLockReadGuard`].
+///
+/// [`map`]: RwLockReadGuard::map
+#[derive(Debug)]
+pub struct RwLockMappedReadGuard<'a, T> {
+    permit: ReleasingPermit<'a, T>,
+    value: *const T,
+    marker: PhantomData<&'a T>,
+}
+
+/// RAII structure used to release the exclusive write access of a lock when
+/// dropped, and which can point at a component of the locked data.
+///
+/// This structure is created by the [`map`] method on [`RwLockWriteGuard`].
+///
+/// [`map`]: RwLockWriteGuard::map
+#[derive(Debug)]
+pub struct RwLockMappedWriteGuard<'a, T> {
+    permit: ReleasingPermit<'a, T>,
+    value: *mut T,
+    marker: PhantomData<&'a mut T>,
+}
+
+// Either a borrowed or an owned handle to the `RwLock`'s semaphore, so that
+// `ReleasingPermit` can release back to the right place regardless of
+// whether the guard that created it borrows the lock or owns an `Arc` to it.
+// `Borrowed` only needs the semaphore itself (not the whole `RwLock<T>`), so
+// that mapped guards, whose data pointer may no longer point at a `T`, can
+// still reuse `ReleasingPermit` to release their permit.
+#[derive(Debug)]
+enum LockRef<'a, T> {
+    Borrowed(&'a Semaphore),
+    Owned(Arc<RwLock<T>>),
+}
+
+impl<'a, T> LockRef<'a, T> {
+    fn semaphore(&self) -> &Semaphore {
+        match self {
+            LockRef::Borrowed(s) => s,
+            LockRef::Owned(lock) => &lock.s,
+        }
+    }
+
+    // Only ever called through an owned guard's `ReleasingPermit`, which is
+    // always built from `LockRef::Owned`.
+    fn owned(&self) -> &RwLock<T> {
+        match self {
+            LockRef::Borrowed(_) => unreachable!("borrowed guards have no owned RwLock"),
+            LockRef::Owned(lock) => lock,
+        }
+    }
+}
+
+// The number of `Permit`s needed to hold `num_permits` permits, each holding
+// at most `ACQUIRE_CHUNK`.
+fn chunk_count(num_permits: usize) -> usize {
+    if num_permits == 0 {
+        0
+    } else {
+        (num_permits - 1) / ACQUIRE_CHUNK as usize + 1
+    }
+}
+
+// How many permits the `index`-th chunk of a `num_permits`-sized hold
+// accounts for.
+fn chunk_size(num_permits: usize, index: usize) -> u16 {
+    let remaining = num_permits - index * ACQUIRE_CHUNK as usize;
+    cmp::min(remaining, ACQUIRE_CHUNK as usize) as u16
+}
+
+// Wrapper around one or more `Permit`s that releases them on `Drop`. A write
+// lock's `MAX_READS`-sized hold doesn't fit in a single `Permit` (see
+// `ACQUIRE_CHUNK`), so it's spread across `permits`, each holding
+// `chunk_size(num_permits, index)` permits; every other guard just uses a
+// single-element `permits`.
 #[derive(Debug)]
 struct ReleasingPermit<'a, T> {
-    num_permits: u16,
-    permit: Permit,
-    lock: &'a RwLock<T>,
+    num_permits: usize,
+    permits: Vec<Permit>,
+    lock: LockRef<'a, T>,
 }
 
 impl<'a, T> ReleasingPermit<'a, T> {
-    fn poll_acquire(
-        &mut self,
-        cx: &mut Context<'_>,
-        s: &Semaphore,
-    ) -> Poll<Result<(), AcquireError>> {
-        self.permit.poll_acquire(cx, self.num_permits, s)
+    fn new(num_permits: usize, lock: LockRef<'a, T>) -> Self {
+        ReleasingPermit {
+            num_permits,
+            permits: (0..chunk_count(num_permits))
+                .map(|_| Permit::new())
+                .collect(),
+            lock,
+        }
+    }
+
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), AcquireError>> {
+        let semaphore = self.lock.semaphore();
+        let num_permits = self.num_permits;
+        for (i, permit) in self.permits.iter_mut().enumerate() {
+            match permit.poll_acquire(cx, chunk_size(num_permits, i), semaphore) {
+                Poll::Ready(Ok(())) => {}
+                not_ready_or_err => return not_ready_or_err,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn try_acquire(&mut self) -> Result<(), TryAcquireError> {
+        let semaphore = self.lock.semaphore();
+        let num_permits = self.num_permits;
+        for i in 0..self.permits.len() {
+            if let Err(e) = self.permits[i].try_acquire(chunk_size(num_permits, i), semaphore) {
+                // Hand back whichever earlier chunks we did acquire before
+                // this one failed, then empty `self.permits` so `Drop`
+                // doesn't try to release them (or this failed chunk) again.
+                for (j, acquired) in self.permits[..i].iter_mut().enumerate() {
+                    acquired.release(chunk_size(num_permits, j), semaphore);
+                }
+                self.permits.clear();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    // Shrinks this already-acquired permit down to `new_num_permits`,
+    // releasing the excess chunks/counts back to the semaphore, and hands
+    // back a fresh `ReleasingPermit` (possibly for a different logical type
+    // `U`) that carries the retained `Permit`s forward as-is, since after
+    // the releases below they already accurately reflect the new, smaller
+    // hold -- replacing them with fresh `Permit::new()`s instead would
+    // believe they hold zero and release nothing on drop, permanently
+    // leaking `new_num_permits` permits.
+    //
+    // Only ever called on permits taken out by `RwLockReadGuard`/
+    // `RwLockWriteGuard`, which always borrow the semaphore, so the `Owned`
+    // case can't happen here.
+    fn shrink_and_retarget<U>(mut self, new_num_permits: usize) -> ReleasingPermit<'a, U> {
+        let semaphore = match &self.lock {
+            LockRef::Borrowed(s) => *s,
+            LockRef::Owned(_) => unreachable!("owned guards are never downgraded or mapped"),
+        };
+
+        let new_chunk_count = chunk_count(new_num_permits);
+        for (i, permit) in self.permits[new_chunk_count..].iter_mut().enumerate() {
+            permit.release(chunk_size(self.num_permits, new_chunk_count + i), semaphore);
+        }
+        self.permits.truncate(new_chunk_count);
+        if let Some(last) = self.permits.last_mut() {
+            let last_index = new_chunk_count - 1;
+            let old_size = chunk_size(self.num_permits, last_index);
+            let new_size = chunk_size(new_num_permits, last_index);
+            if new_size < old_size {
+                last.release(old_size - new_size, semaphore);
+            }
+        }
+
+        ReleasingPermit {
+            num_permits: new_num_permits,
+            permits: mem::take(&mut self.permits),
+            lock: LockRef::Borrowed(semaphore),
+        }
+    }
+
+    // Re-targets this already-acquired permit at a different logical type,
+    // for use by mapped guards whose data pointer may no longer point at a
+    // `T`.
+    fn retarget<U>(self) -> ReleasingPermit<'a, U> {
+        let num_permits = self.num_permits;
+        self.shrink_and_retarget(num_permits)
     }
 }
 
 impl<'a, T> Drop for ReleasingPermit<'a, T> {
     fn drop(&mut self) {
-        self.permit.release(self.num_permits, &self.lock.s);
+        let semaphore = self.lock.semaphore();
+        let num_permits = self.num_permits;
+        for (i, permit) in self.permits.iter_mut().enumerate() {
+            permit.release(chunk_size(num_permits, i), semaphore);
+        }
     }
 }
 
@@ -130,6 +325,10 @@ unsafe impl<T> Send for RwLock<T> where T: Send {}
 unsafe impl<T> Sync for RwLock<T> where T: Send + Sync {}
 unsafe impl<'a, T> Sync for RwLockReadGuard<'a, T> where T: Send + Sync {}
 unsafe impl<'a, T> Sync for RwLockWriteGuard<'a, T> where T: Send + Sync {}
+unsafe impl<T> Sync for OwnedRwLockReadGuard<T> where T: Send + Sync {}
+unsafe impl<T> Sync for OwnedRwLockWriteGuard<T> where T: Send + Sync {}
+unsafe impl<'a, T> Sync for RwLockMappedReadGuard<'a, T> where T: Send + Sync {}
+unsafe impl<'a, T> Sync for RwLockMappedWriteGuard<'a, T> where T: Send + Sync {}
 
 impl<T> RwLock<T> {
     /// Creates a new instance of an `RwLock<T>` which is unlocked.
@@ -141,6 +340,10 @@ impl<T> RwLock<T> {
     ///
     /// let lock = RwLock::new(5);
     /// ```
+    // `Semaphore::new` isn't known to be a `const fn` (it lives in
+    // `semaphore_ll`, which this patch doesn't touch), so this can't be
+    // marked `const` without risking a build break. Revisit once
+    // `semaphore_ll::Semaphore::new` is confirmed `const`.
     pub fn new(value: T) -> RwLock<T> {
         RwLock {
             c: UnsafeCell::new(value),
@@ -176,13 +379,9 @@ impl<T> RwLock<T> {
     ///}
     /// ```
     pub async fn read(&self) -> RwLockReadGuard<'_, T> {
-        let mut permit = ReleasingPermit {
-            num_permits: 1,
-            permit: Permit::new(),
-            lock: self,
-        };
+        let mut permit = ReleasingPermit::new(1, LockRef::Borrowed(&self.s));
 
-        poll_fn(|cx| permit.poll_acquire(cx, &self.s))
+        poll_fn(|cx| permit.poll_acquire(cx))
             .await
             .unwrap_or_else(|_| {
                 // The semaphore was closed. but, we never explicitly close it, and we have a
@@ -192,6 +391,48 @@ impl<T> RwLock<T> {
         RwLockReadGuard { lock: self, permit }
     }
 
+    /// Locks this rwlock with shared read access, blocking the current task
+    /// until it can be acquired.
+    ///
+    /// This is like [`read`], but the returned guard owns a clone of the
+    /// `Arc<RwLock<T>>` rather than borrowing it, which makes it possible to
+    /// move into a `'static` task spawned with [`tokio::spawn`].
+    ///
+    /// [`read`]: RwLock::read
+    /// [`tokio::spawn`]: crate::spawn
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = Arc::new(RwLock::new(1));
+    ///
+    ///     let n = lock.clone().read_owned().await;
+    ///     assert_eq!(*n, 1);
+    ///
+    ///     tokio::spawn(async move {
+    ///         let r = lock.read_owned().await;
+    ///         assert_eq!(*r, 1);
+    ///     });
+    /// }
+    /// ```
+    pub async fn read_owned(self: Arc<Self>) -> OwnedRwLockReadGuard<T> {
+        let mut permit = ReleasingPermit::new(1, LockRef::Owned(self));
+
+        poll_fn(|cx| permit.poll_acquire(cx))
+            .await
+            .unwrap_or_else(|_| {
+                // The semaphore was closed. but, we never explicitly close it, and we have a
+                // handle to it through the Arc, which means that this can never happen.
+                unreachable!()
+            });
+        OwnedRwLockReadGuard { permit }
+    }
+
     /// Locks this rwlock with exclusive write access, blocking the current
     /// task until it can be acquired.
     ///
@@ -215,13 +456,9 @@ impl<T> RwLock<T> {
     ///}
     /// ```
     pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
-        let mut permit = ReleasingPermit {
-            num_permits: MAX_READS as u16,
-            permit: Permit::new(),
-            lock: self,
-        };
+        let mut permit = ReleasingPermit::new(MAX_READS, LockRef::Borrowed(&self.s));
 
-        poll_fn(|cx| permit.poll_acquire(cx, &self.s))
+        poll_fn(|cx| permit.poll_acquire(cx))
             .await
             .unwrap_or_else(|_| {
                 // The semaphore was closed. but, we never explicitly close it, and we have a
@@ -231,8 +468,366 @@ impl<T> RwLock<T> {
 
         RwLockWriteGuard { lock: self, permit }
     }
+
+    /// Locks this rwlock with exclusive write access, blocking the current
+    /// task until it can be acquired.
+    ///
+    /// This is like [`write`], but the returned guard owns a clone of the
+    /// `Arc<RwLock<T>>` rather than borrowing it, which makes it possible to
+    /// move into a `'static` task spawned with [`tokio::spawn`].
+    ///
+    /// [`write`]: RwLock::write
+    /// [`tokio::spawn`]: crate::spawn
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = Arc::new(RwLock::new(1));
+    ///
+    ///     let mut n = lock.clone().write_owned().await;
+    ///     *n = 2;
+    /// }
+    /// ```
+    pub async fn write_owned(self: Arc<Self>) -> OwnedRwLockWriteGuard<T> {
+        let mut permit = ReleasingPermit::new(MAX_READS, LockRef::Owned(self));
+
+        poll_fn(|cx| permit.poll_acquire(cx))
+            .await
+            .unwrap_or_else(|_| {
+                // The semaphore was closed. but, we never explicitly close it, and we have a
+                // handle to it through the Arc, which means that this can never happen.
+                unreachable!()
+            });
+
+        OwnedRwLockWriteGuard { permit }
+    }
+
+    /// Attempts to acquire this rwlock with shared read access.
+    ///
+    /// If the access couldn't be acquired immediately, returns [`TryLockError`].
+    /// Otherwise, an RAII guard is returned which releases read access when
+    /// dropped.
+    ///
+    /// [`TryLockError`]: TryLockError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(1);
+    ///
+    ///     match lock.try_read() {
+    ///         Ok(n) => assert_eq!(*n, 1),
+    ///         Err(_) => unreachable!(),
+    ///     };
+    /// }
+    /// ```
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
+        let mut permit = ReleasingPermit::new(1, LockRef::Borrowed(&self.s));
+
+        permit.try_acquire().map_err(|_| TryLockError(()))?;
+
+        Ok(RwLockReadGuard { lock: self, permit })
+    }
+
+    /// Attempts to acquire this rwlock with exclusive write access.
+    ///
+    /// If the access couldn't be acquired immediately, returns [`TryLockError`].
+    /// Otherwise, an RAII guard is returned which releases write access when
+    /// dropped.
+    ///
+    /// [`TryLockError`]: TryLockError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(1);
+    ///
+    ///     let n = lock.read().await;
+    ///     assert_eq!(*n, 1);
+    ///
+    ///     assert!(lock.try_write().is_err());
+    /// }
+    /// ```
+    pub fn try_write(&self) -> Result<RwLockWriteGuard<'_, T>, TryLockError> {
+        let mut permit = ReleasingPermit::new(MAX_READS, LockRef::Borrowed(&self.s));
+
+        permit.try_acquire().map_err(|_| TryLockError(()))?;
+
+        Ok(RwLockWriteGuard { lock: self, permit })
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the `RwLock` mutably, no actual locking needs
+    /// to take place -- the mutable borrow statically guarantees no locks
+    /// are held.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    ///
+    /// fn main() {
+    ///     let mut lock = RwLock::new(1);
+    ///
+    ///     let n = lock.get_mut();
+    ///     *n = 2;
+    /// }
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe {
+            // Safe since we have an exclusive reference to `self`.
+            &mut *self.c.get()
+        }
+    }
+
+    /// Consumes the lock, returning the underlying data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    ///
+    /// fn main() {
+    ///     let lock = RwLock::new(1);
+    ///
+    ///     let n = lock.into_inner();
+    ///     assert_eq!(n, 1);
+    /// }
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.c.into_inner()
+    }
+}
+
+impl<'a, T> RwLockWriteGuard<'a, T> {
+    /// Atomically downgrades a write lock into a read lock, without allowing
+    /// any writers to take the lock in the meantime.
+    ///
+    /// This method releases all but one of the write permits held by `self`
+    /// and hands that remaining permit to the returned [`RwLockReadGuard`],
+    /// so there is no window in which the lock is briefly unlocked and a
+    /// writer could acquire it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = Arc::new(RwLock::new(1));
+    ///
+    ///     let mut write_guard = lock.write().await;
+    ///     *write_guard += 1;
+    ///
+    ///     let read_guard = write_guard.downgrade();
+    ///     assert_eq!(*read_guard, 2);
+    /// }
+    /// ```
+    pub fn downgrade(self) -> RwLockReadGuard<'a, T> {
+        let RwLockWriteGuard { lock, permit } = self;
+
+        // Shrink the write permit down to a single read permit, releasing
+        // the rest back to the semaphore, and carry the retained permit
+        // forward directly rather than dropping the write permit and
+        // acquiring a fresh read one, so there's no window in which the lock
+        // is briefly unlocked and a writer could acquire it.
+        RwLockReadGuard {
+            lock,
+            permit: permit.shrink_and_retarget(1),
+        }
+    }
+}
+
+impl<'a, T> RwLockReadGuard<'a, T> {
+    /// Makes a new `RwLockMappedReadGuard` for a component of the locked
+    /// data.
+    ///
+    /// This operation cannot fail as the `RwLockReadGuard` passed in already
+    /// locked the data, and a read lock is kept until the returned guard is
+    /// dropped.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockReadGuard::map(...)`. A method would interfere with methods of
+    /// the same name on the contents of the locked data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::{RwLock, RwLockReadGuard};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(vec![1, 2, 3]);
+    ///
+    ///     let guard = lock.read().await;
+    ///     let guard = RwLockReadGuard::map(guard, |v| &v[0]);
+    ///     assert_eq!(*guard, 1);
+    /// }
+    /// ```
+    pub fn map<U, F>(this: Self, f: F) -> RwLockMappedReadGuard<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let value = f(&*this) as *const U;
+        let RwLockReadGuard { lock: _, permit } = this;
+
+        RwLockMappedReadGuard {
+            permit: permit.retarget(),
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// Attempts to make a new `RwLockMappedReadGuard` for a component of the
+    /// locked data. Returns the original guard as the error value if the
+    /// closure returns `None`.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockReadGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the locked data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::{RwLock, RwLockReadGuard};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(vec![1, 2, 3]);
+    ///
+    ///     let guard = lock.read().await;
+    ///     let guard = RwLockReadGuard::try_map(guard, |v| v.get(0)).expect("index 0 exists");
+    ///     assert_eq!(*guard, 1);
+    /// }
+    /// ```
+    pub fn try_map<U, F>(this: Self, f: F) -> Result<RwLockMappedReadGuard<'a, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        let value = match f(&*this) {
+            Some(value) => value as *const U,
+            None => return Err(this),
+        };
+        let RwLockReadGuard { lock: _, permit } = this;
+
+        Ok(RwLockMappedReadGuard {
+            permit: permit.retarget(),
+            value,
+            marker: PhantomData,
+        })
+    }
 }
 
+impl<'a, T> RwLockWriteGuard<'a, T> {
+    /// Makes a new `RwLockMappedWriteGuard` for a component of the locked
+    /// data.
+    ///
+    /// This operation cannot fail as the `RwLockWriteGuard` passed in already
+    /// locked the data, and an exclusive lock is kept until the returned
+    /// guard is dropped.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockWriteGuard::map(...)`. A method would interfere with methods
+    /// of the same name on the contents of the locked data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::{RwLock, RwLockWriteGuard};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(vec![1, 2, 3]);
+    ///
+    ///     let mut guard = RwLockWriteGuard::map(lock.write().await, |v| &mut v[0]);
+    ///     *guard = 2;
+    ///     assert_eq!(*guard, 2);
+    /// }
+    /// ```
+    pub fn map<U, F>(mut this: Self, f: F) -> RwLockMappedWriteGuard<'a, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value = f(&mut *this) as *mut U;
+        let RwLockWriteGuard { lock: _, permit } = this;
+
+        RwLockMappedWriteGuard {
+            permit: permit.retarget(),
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// Attempts to make a new `RwLockMappedWriteGuard` for a component of
+    /// the locked data. Returns the original guard as the error value if
+    /// the closure returns `None`.
+    ///
+    /// This is an associated function that needs to be used as
+    /// `RwLockWriteGuard::try_map(...)`. A method would interfere with
+    /// methods of the same name on the contents of the locked data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::{RwLock, RwLockWriteGuard};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let lock = RwLock::new(vec![1, 2, 3]);
+    ///
+    ///     let mut guard =
+    ///         RwLockWriteGuard::try_map(lock.write().await, |v| v.get_mut(0)).expect("index 0 exists");
+    ///     *guard = 2;
+    ///     assert_eq!(*guard, 2);
+    /// }
+    /// ```
+    pub fn try_map<U, F>(mut this: Self, f: F) -> Result<RwLockMappedWriteGuard<'a, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let value = match f(&mut *this) {
+            Some(value) => value as *mut U,
+            None => return Err(this),
+        };
+        let RwLockWriteGuard { lock: _, permit } = this;
+
+        Ok(RwLockMappedWriteGuard {
+            permit: permit.retarget(),
+            value,
+            marker: PhantomData,
+        })
+    }
+}
+
+/// Error returned when the lock could not be acquired without blocking, e.g.
+/// by [`RwLock::try_read`] or [`RwLock::try_write`].
+#[derive(Debug)]
+pub struct TryLockError(());
+
+impl fmt::Display for TryLockError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("operation would block")
+    }
+}
+
+impl Error for TryLockError {}
+
 impl<T> ops::Deref for RwLockReadGuard<'_, T> {
     type Target = T;
 
@@ -254,3 +849,163 @@ impl<T> ops::DerefMut for RwLockWriteGuard<'_, T> {
         unsafe { &mut *self.lock.c.get() }
     }
 }
+
+impl<T> ops::Deref for OwnedRwLockReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.permit.lock.owned().c.get() }
+    }
+}
+
+impl<T> ops::Deref for OwnedRwLockWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.permit.lock.owned().c.get() }
+    }
+}
+
+impl<T> ops::DerefMut for OwnedRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.permit.lock.owned().c.get() }
+    }
+}
+
+impl<'a, T> ops::Deref for RwLockMappedReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> ops::Deref for RwLockMappedWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> ops::DerefMut for RwLockMappedWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn try_read_and_try_write_fail_while_write_locked() {
+        let lock = RwLock::new(1);
+        let write_guard = lock.write().await;
+
+        assert!(lock.try_read().is_err());
+        assert!(lock.try_write().is_err());
+
+        drop(write_guard);
+
+        assert!(lock.try_read().is_ok());
+    }
+
+    #[tokio::test]
+    async fn try_write_fails_while_read_locked() {
+        let lock = RwLock::new(1);
+        let _read_guard = lock.read().await;
+
+        assert!(lock.try_write().is_err());
+    }
+
+    #[tokio::test]
+    async fn owned_guards_outlive_the_original_lock() {
+        let lock = Arc::new(RwLock::new(1));
+
+        let write_guard = lock.clone().write_owned().await;
+        drop(lock);
+
+        let value = tokio::spawn(async move {
+            let mut guard = write_guard;
+            *guard += 1;
+            *guard
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(value, 2);
+
+        let lock = Arc::new(RwLock::new(1));
+        let read_guard = lock.clone().read_owned().await;
+        drop(lock);
+
+        let value = tokio::spawn(async move { *read_guard }).await.unwrap();
+        assert_eq!(value, 1);
+    }
+
+    #[tokio::test]
+    async fn downgrade_prevents_writer_from_interleaving() {
+        let lock = Arc::new(RwLock::new(vec![1]));
+
+        let write_guard = lock.write().await;
+        let read_guard = write_guard.downgrade();
+
+        let lock2 = lock.clone();
+        let write_task = tokio::spawn(async move {
+            let mut guard = lock2.write().await;
+            guard.push(2);
+        });
+
+        // Give the spawned writer a chance to run; it must not be able to
+        // acquire the write lock while `read_guard` is still held.
+        tokio::task::yield_now().await;
+        assert_eq!(*read_guard, vec![1]);
+
+        drop(read_guard);
+        write_task.await.unwrap();
+
+        assert_eq!(*lock.read().await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn try_map_returns_original_guard_on_none() {
+        let lock = RwLock::new(vec![1, 2, 3]);
+
+        let read_guard = lock.read().await;
+        let read_guard = match RwLockReadGuard::try_map(read_guard, |v: &Vec<i32>| v.get(10)) {
+            Ok(_) => panic!("try_map should not have succeeded"),
+            Err(guard) => guard,
+        };
+        assert_eq!(*read_guard, vec![1, 2, 3]);
+        drop(read_guard);
+
+        let write_guard = lock.write().await;
+        let write_guard =
+            match RwLockWriteGuard::try_map(write_guard, |v: &mut Vec<i32>| v.get_mut(10)) {
+                Ok(_) => panic!("try_map should not have succeeded"),
+                Err(guard) => guard,
+            };
+        assert_eq!(*write_guard, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn lock_is_reacquirable_after_a_successful_map_is_dropped() {
+        let lock = RwLock::new(vec![1, 2, 3]);
+
+        let read_guard = lock.read().await;
+        let mapped = RwLockReadGuard::map(read_guard, |v| &v[0]);
+        assert_eq!(*mapped, 1);
+        drop(mapped);
+
+        // The mapped guard's permit must actually have been released, not
+        // silently dropped on the floor, or this would hang/fail forever.
+        assert!(lock.try_write().is_ok());
+
+        let write_guard = lock.write().await;
+        let mapped = RwLockWriteGuard::map(write_guard, |v| &mut v[0]);
+        drop(mapped);
+
+        assert!(lock.try_write().is_ok());
+    }
+}